@@ -12,13 +12,31 @@
 //! The contract flow can be summarized in these steps:
 //!
 //! 1. Initialization of contract. The id of the first one is 1 and the list of results is empty.
-//! 2. Users can cast their secret votes. ("false" is against, "true" is for).
-//! 3. At any point can anyone start vote counting.
-//! 4. Zk Computation sums yes votes and no votes, and output each as a separate variable.
-//! 5. When computation is complete the contract will open the output variables.
-//! 6. The result (number of yes votes, number of no votes and number of absent voters) is attested by computation nodes.
-//! 7. The result is added to the list of historic votes, the vote id is incremented and all inputs are deleted.
-//! 8. A new vote has begun, starting from step 3.
+//! 2. Users register themselves as eligible voters. Only a registered address may cast a vote,
+//!    but registration is open-ended, so it need not happen before the first vote.
+//! 3. Users can cast a secret vote, consisting of the candidate they choose and their secret
+//!    voting weight (e.g. a token or stake balance).
+//! 4. At any point can anyone start vote counting.
+//! 5. Zk Computation tallies the weighted votes cast for each candidate and the total weight of
+//!    all ballots, and outputs each as a separate variable.
+//! 6. When computation is complete the contract will open the output variables.
+//! 7. The result (the weighted tally for each candidate, the total weight and the number of
+//!    eligible voters who abstained) is attested by computation nodes, both as one packed,
+//!    EVM-friendly value and as a set of per-digit attestations that let a verifier contract check
+//!    range conditions on a tally without the exact value being revealed by the proof.
+//! 8. The result is added to the list of historic votes, the vote id is incremented and all inputs are deleted.
+//! 9. A new vote has begun, starting from step 4.
+//!
+//! ## Known limitations
+//!
+//! [`VoteResult::proof`] verifies on Ethereum as one `ecrecover` per computation node, which is
+//! O(n) gas and calldata. Aggregating the nodes' attestations into a single FROST-style threshold
+//! signature, so a verifier checks one signature against one group public key, was requested but
+//! is not implemented here: producing a real aggregate requires the computation nodes to run a
+//! distributed signing ceremony (committing to nonces before combining partial signatures) that
+//! this contract has no way to drive, and the nodes' independent per-attestation ECDSA signatures
+//! don't give us that. This is left unimplemented rather than shipped as an unverifiable
+//! placeholder signature.
 
 #[macro_use]
 extern crate pbc_contract_codegen;
@@ -27,30 +45,48 @@ extern crate pbc_contract_common;
 use std::fmt::Write;
 
 use create_type_spec_derive::CreateTypeSpec;
+use pbc_contract_common::address::Address;
 use pbc_contract_common::context::ContractContext;
 use pbc_contract_common::events::EventGroup;
 use pbc_contract_common::signature::Signature;
 use pbc_contract_common::zk::AttestationId;
 use pbc_contract_common::zk::{CalculationStatus, SecretVarId, ZkInputDef, ZkState, ZkStateChange};
 use pbc_traits::WriteRPC;
-use pbc_zk::{Sbi1, SecretBinary};
+use pbc_zk::{Sbi32, Sbi8, SecretBinary};
 use read_write_state_derive::ReadWriteState;
 
 /// Structure representing the metadata attached to a secret variable in the ZK state,
 /// indicating which type of variable it is.
-/// For this voting example, a secret variable can either be a user inputted vote, or the result of
-/// running the ZK computation, i.e. the final count of "yes" votes.
-#[derive(ReadWriteState, Debug)]
+/// For this voting example, a secret variable can either be a user inputted vote, or one of the
+/// results of running the ZK computation, i.e. a candidate's tally or the total voting weight.
+#[derive(ReadWriteState, Debug, Clone)]
 #[repr(C)]
 struct SecretVarMetadata {
     variable_type: SecretVarType,
 }
 
-#[derive(ReadWriteState, Debug, PartialEq)]
+#[derive(ReadWriteState, Debug, Clone, PartialEq)]
 #[repr(u8)]
 enum SecretVarType {
     Vote = 1,
-    CountedYesVotes = 2,
+    CountedCandidateTally = 2,
+    CountedTotalWeight = 3,
+}
+
+/// Proof that a single base-`digit_base` digit of a candidate's tally was attested by the
+/// computation nodes, binding `(vote_id, candidate, digit_position, digit_value)` so that a
+/// verifier contract can check `tally \in [a, c]` from the minimal set of digit prefixes that
+/// cover the interval, without learning the exact tally.
+#[derive(ReadWriteState, CreateTypeSpec, Clone)]
+struct DigitAttestationProof {
+    /// The candidate this digit belongs to.
+    candidate: u32,
+    /// The digit's position (the exponent `i` in `digit_value = (tally / base^i) % base`).
+    digit_position: u32,
+    /// The value of the digit.
+    digit_value: u32,
+    /// Proof of the digit that can be validated on Ethereum contract.
+    proof: String,
 }
 
 /// Result of a vote after counting is complete.
@@ -60,12 +96,37 @@ enum SecretVarType {
 struct VoteResult {
     /// The identifier for the vote the result is valid for.
     vote_id: u32,
-    /// Number of votes cast in favor.
-    votes_for: u32,
-    /// Number of votes cast against.
-    votes_against: u32,
+    /// Weighted tally of votes cast for each candidate, indexed by candidate number.
+    tally: Vec<u32>,
+    /// Total voting weight represented by all ballots cast in this vote.
+    total_weight: u32,
     /// Proof of the vote result that can be validated on Ethereum contract.
     proof: Option<String>,
+    /// Per-digit attestation proofs, one per candidate per digit position, filled in as each
+    /// digit's attestation completes, so the list is partial until all have arrived.
+    digit_proofs: Vec<DigitAttestationProof>,
+    /// Whether the vote passed, i.e. turnout met [`ContractState::quorum`] and the leading
+    /// candidate's share of the total weight met [`ContractState::approval_threshold`].
+    passed: bool,
+    /// The quorum that was in effect for this vote.
+    quorum: u32,
+    /// The approval threshold that was in effect for this vote, in parts per
+    /// [`APPROVAL_THRESHOLD_DENOMINATOR`].
+    approval_threshold: u32,
+    /// Number of eligible voters who did not cast a ballot in this vote.
+    abstained: u32,
+}
+
+/// A single attestation the contract is waiting on for the vote currently being finalized, at the
+/// same index the matching [`ZkStateChange::Attest`] was requested at in
+/// `build_and_attest_voting_result`. Looked up by that index rather than consumed in delivery
+/// order, since completions are not guaranteed to arrive in the order they were requested.
+#[derive(ReadWriteState, Clone)]
+enum PendingAttestation {
+    /// The packed, EVM-friendly encoding of the whole vote result.
+    PackedResult,
+    /// A single base-`digit_base` digit of a candidate's tally.
+    Digit { candidate: u32, digit_position: u32 },
 }
 
 /// Structure representing the open state for private voting contract.
@@ -75,26 +136,111 @@ struct ContractState {
     current_vote_id: u32,
     /// List of result for all votes that have been resolved.
     vote_results: Vec<VoteResult>,
+    /// Base used to decompose each candidate's tally into digits for the digit-attestation proof
+    /// mode. Constant across all votes so that digit prefixes line up for a verifier contract.
+    digit_base: u32,
+    /// Number of base-[`ContractState::digit_base`] digits used to decompose each candidate's
+    /// tally. Constant across all votes so that digit prefixes line up for a verifier contract.
+    digit_count: u32,
+    /// What each attestation requested for the vote currently being finalized is for, indexed by
+    /// the order the corresponding [`ZkStateChange::Attest`] was requested in.
+    pending_attestations: Vec<PendingAttestation>,
+    /// Number of attestations requested for the vote currently being finalized that have not yet
+    /// completed. Once this reaches zero, the vote can be finalized and the next one started.
+    attestations_remaining: u32,
+    /// Minimum total voting weight ([`VoteResult::total_weight`]) a vote must reach to pass,
+    /// regardless of the outcome.
+    quorum: u32,
+    /// Minimum share of the total voting weight the leading candidate must reach for a vote to
+    /// pass, in parts per [`APPROVAL_THRESHOLD_DENOMINATOR`].
+    approval_threshold: u32,
+    /// Addresses allowed to cast a vote. Registration is open-ended: an address stays eligible
+    /// for every vote held after it registers.
+    eligible_voters: Vec<Address>,
 }
 
 /// Method for initializing the contract's state. To make the ids of the vote result match the ids
 /// of the data attestations, the first vote has id 1.
+///
+/// `digit_base` and `digit_count` configure the digit-attestation proof mode: each candidate's
+/// tally is decomposed into `digit_count` base-`digit_base` digits. These must stay the same for
+/// the lifetime of the contract, so that digit prefixes from different votes remain comparable.
+/// `digit_base` must be at least 2, and `digit_base`/`digit_count` must be small enough that the
+/// highest digit's place value, `digit_base.pow(digit_count - 1)`, fits in a `u32`: both are
+/// checked here since a bad combination would otherwise panic on every future vote finalization.
+///
+/// `quorum` and `approval_threshold` configure the passing condition computed for every vote, see
+/// [`ContractState::quorum`] and [`ContractState::approval_threshold`].
 #[init(zk = true)]
-fn initialize(_ctx: ContractContext, _zk_state: ZkState<SecretVarMetadata>) -> ContractState {
+fn initialize(
+    _ctx: ContractContext,
+    _zk_state: ZkState<SecretVarMetadata>,
+    digit_base: u32,
+    digit_count: u32,
+    quorum: u32,
+    approval_threshold: u32,
+) -> ContractState {
+    assert!(
+        digit_base >= 2,
+        "digit_base must be at least 2, but was {digit_base}"
+    );
+    assert!(
+        digit_base.checked_pow(digit_count.saturating_sub(1)).is_some(),
+        "digit_base={digit_base} raised to the highest digit position ({}) overflows u32; reduce digit_base or digit_count",
+        digit_count.saturating_sub(1)
+    );
     ContractState {
         current_vote_id: 1,
         vote_results: vec![],
+        digit_base,
+        digit_count,
+        pending_attestations: vec![],
+        attestations_remaining: 0,
+        quorum,
+        approval_threshold,
+        eligible_voters: vec![],
     }
 }
 
-/// The bit size of the secret vote. A vote can either be 0 or 1, so a single bit is needed.
-const BITLENGTH_OF_SECRET_VOTE_VARIABLES: u32 = 1;
+/// Registers the caller as eligible to cast a vote in this and every future vote held by this
+/// contract.
+#[action(shortname = 0x02)]
+fn register_eligible_voter(
+    context: ContractContext,
+    mut state: ContractState,
+) -> (ContractState, Vec<EventGroup>) {
+    assert!(
+        !state.eligible_voters.contains(&context.sender),
+        "Address is already registered as an eligible voter: {:?}",
+        context.sender
+    );
+    state.eligible_voters.push(context.sender);
+    (state, vec![])
+}
+
+/// The number of candidates a ballot can choose between. A vote carries the index of the chosen
+/// candidate, in the range `0..NUM_CANDIDATES`.
+const NUM_CANDIDATES: usize = 8;
+
+/// The bit size of the candidate field of the secret vote. A vote is a candidate index in
+/// `0..NUM_CANDIDATES`, which fits in a single byte.
+const BITLENGTH_OF_CANDIDATE_VARIABLE: u32 = 8;
+
+/// The bit size of the weight field of the secret vote, e.g. a token or stake balance.
+const BITLENGTH_OF_WEIGHT_VARIABLE: u32 = 32;
 
-/// A secret vote. False means against and true means for.
+/// The denominator [`ContractState::approval_threshold`] is expressed in parts per, e.g. an
+/// approval threshold of `5_000` means the leading candidate must reach at least 50% of the total
+/// voting weight.
+const APPROVAL_THRESHOLD_DENOMINATOR: u32 = 10_000;
+
+/// A secret vote, holding the index of the candidate the voter chose together with the voter's
+/// secret voting weight (e.g. token or stake balance), so that ballots need not count equally.
 #[derive(CreateTypeSpec, SecretBinary)]
 #[allow(dead_code)]
 struct SecretVote {
-    vote: Sbi1,
+    candidate: Sbi8,
+    weight: Sbi32,
 }
 
 /// A voter can cast a secret vote using this function.
@@ -104,8 +250,9 @@ struct SecretVote {
 ///
 /// The type of input is specified as the SecretVote struct defined above.
 ///
-/// The ZkInputDef encodes that the secret vote must have size
-/// [`BITLENGTH_OF_SECRET_VOTE_VARIABLES`].
+/// The ZkInputDef encodes that the secret vote consists of a candidate field of size
+/// [`BITLENGTH_OF_CANDIDATE_VARIABLE`] followed by a weight field of size
+/// [`BITLENGTH_OF_WEIGHT_VARIABLE`].
 #[zk_on_secret_input(shortname = 0x40, secret_type = "SecretVote")]
 fn cast_vote(
     context: ContractContext,
@@ -125,6 +272,13 @@ fn cast_vote(
         zk_state.calculation_state,
     );
 
+    // Ensure that only registered addresses can cast a vote.
+    assert!(
+        state.eligible_voters.contains(&context.sender),
+        "Only eligible voters may cast a vote. Sender: {:?}",
+        context.sender
+    );
+
     // Ensure that the account casting the vote has not already voted in this round.
     assert!(
         zk_state
@@ -142,7 +296,7 @@ fn cast_vote(
         metadata: SecretVarMetadata {
             variable_type: SecretVarType::Vote,
         },
-        expected_bit_lengths: vec![BITLENGTH_OF_SECRET_VOTE_VARIABLES],
+        expected_bit_lengths: vec![BITLENGTH_OF_CANDIDATE_VARIABLE, BITLENGTH_OF_WEIGHT_VARIABLE],
     };
     // Return the state as is, no events and the input definition of the variable.
     (state, vec![], input_def)
@@ -175,13 +329,21 @@ fn start_vote_counting(
     );
 
     // Return the state unmodified, and no events. Request that the computation begins and define
-    // metadata to be attached to the secret output variable.
+    // metadata to be attached to each secret output variable: one per candidate tally, followed by
+    // the total voting weight across all ballots.
+    let mut output_metadata = vec![
+        SecretVarMetadata {
+            variable_type: SecretVarType::CountedCandidateTally,
+        };
+        NUM_CANDIDATES
+    ];
+    output_metadata.push(SecretVarMetadata {
+        variable_type: SecretVarType::CountedTotalWeight,
+    });
     (
         state,
         vec![],
-        vec![ZkStateChange::start_computation(vec![SecretVarMetadata {
-            variable_type: SecretVarType::CountedYesVotes,
-        }])],
+        vec![ZkStateChange::start_computation(output_metadata)],
     )
 }
 
@@ -189,14 +351,14 @@ fn start_vote_counting(
 ///
 /// The only thing we do is to instantly open/declassify the output variables.
 #[zk_on_compute_complete]
-fn open_yes_count_variable(
+fn open_computation_result_variables(
     _context: ContractContext,
     state: ContractState,
     _zk_state: ZkState<SecretVarMetadata>,
     output_variables: Vec<SecretVarId>,
 ) -> (ContractState, Vec<EventGroup>, Vec<ZkStateChange>) {
-    // Immediately request that the output variable, i.e. the count of yes votes, is opened and
-    // made public.
+    // Immediately request that the output variables, i.e. the per-candidate tallies and the total
+    // voting weight, are opened and made public.
     (
         state,
         vec![],
@@ -208,9 +370,12 @@ fn open_yes_count_variable(
 
 /// Automatically called when a variable is opened/declassified.
 ///
-/// We can now read the for and against variables, and compute the result.
+/// We can now read the per-candidate tally variables, and compute the result.
 /// Once the result has been computed we request that the Zk nodes attest the result (i.e sign it)
-/// and save it to this contracts open state.
+/// and save it to this contracts open state. Alongside the packed result, we also request a
+/// separate attestation of each digit of each candidate's tally (see
+/// [`ContractState::digit_base`]/[`ContractState::digit_count`]), so that a verifier contract can
+/// check range conditions on a tally without the full value being revealed by the proof.
 #[zk_on_variables_opened]
 fn build_and_attest_voting_result(
     _context: ContractContext,
@@ -218,28 +383,57 @@ fn build_and_attest_voting_result(
     zk_state: ZkState<SecretVarMetadata>,
     opened_variables: Vec<SecretVarId>,
 ) -> (ContractState, Vec<EventGroup>, Vec<ZkStateChange>) {
-    // Get the id of the variable that was opened after the computation was completed.
-    let computation_result_variable_id = opened_variables.get(0).unwrap();
     // Build the result of the vote by getting the raw numbers from the opened variables and the
     // state.
-    let vote_result = determine_result(&state, &zk_state, computation_result_variable_id);
-    // Add the result to the open state. The result is still missing the proof.
-    state.vote_results.push(vote_result.clone());
+    let vote_result = determine_result(&state, &zk_state, &opened_variables);
+    let vote_id = vote_result.vote_id;
+
+    // Request attestation of the packed result first, followed by one attestation per digit of
+    // each candidate's tally. `pending_attestations` records what each requested attestation is
+    // for, at the same index it is requested at here: since `zk_state.data_attestations` reflects
+    // this same request order regardless of the order completions are later delivered in, that
+    // index - not delivery order - is what ties a completion back to its pending attestation.
+    let mut pending_attestations = vec![PendingAttestation::PackedResult];
+    let mut attests = vec![ZkStateChange::Attest {
+        data_to_attest: serialize_result_as_big_endian(vote_result.clone()),
+    }];
+    for (candidate, tally) in vote_result.tally.iter().enumerate() {
+        let candidate = candidate as u32;
+        for (digit_position, digit_value) in
+            decompose_into_digits(*tally, state.digit_base, state.digit_count)
+        {
+            pending_attestations.push(PendingAttestation::Digit {
+                candidate,
+                digit_position,
+            });
+            attests.push(ZkStateChange::Attest {
+                data_to_attest: serialize_digit_attestation(
+                    vote_id,
+                    candidate,
+                    digit_position,
+                    digit_value,
+                ),
+            });
+        }
+    }
+    state.attestations_remaining = pending_attestations.len() as u32;
+    state.pending_attestations = pending_attestations;
+
+    // Add the result to the open state. The result is still missing the proof and digit proofs.
+    state.vote_results.push(vote_result);
     // Return the tuple with the modified state, no events, and with a request that the computation
-    // nodes sign the serialized bytes of the result.
-    (
-        state,
-        vec![],
-        vec![ZkStateChange::Attest {
-            data_to_attest: serialize_result_as_big_endian(vote_result),
-        }],
-    )
+    // nodes sign the serialized bytes of the result and of every tally digit.
+    (state, vec![], attests)
 }
 
-/// Automatically called once all nodes have signed the data we requested.
+/// Automatically called once a single requested attestation has been signed by all nodes. This
+/// fires once per [`ZkStateChange::Attest`] requested in [`build_and_attest_voting_result`], so it
+/// may run many times (once for the packed result, then once per tally digit) before a vote is
+/// fully finalized.
 ///
-/// Get the signatures for the attestation, formats them for EVM, and adds as proof on the result.
-/// Then delete all variables from the old vote, set the id for the next one and set the
+/// Gets the signatures for the attestation, formats them for EVM, and saves them as proof of
+/// whichever pending attestation completed. Once every pending attestation for the vote has
+/// completed, deletes all variables from the old vote, sets the id for the next one and sets the
 /// calculation status back to "waiting" so we can receive new secret voting inputs for the new
 /// vote.
 #[zk_on_attestation_complete]
@@ -249,44 +443,77 @@ fn save_attestation_on_result_and_start_next_vote(
     zk_state: ZkState<SecretVarMetadata>,
     attestation_id: AttestationId,
 ) -> (ContractState, Vec<EventGroup>, Vec<ZkStateChange>) {
-    // Get ids of all secret variables, to delete all votes cast in the previous vote before
-    // starting the next one.
-    let variables_to_delete: Vec<SecretVarId> = zk_state
-        .secret_variables
-        .iter()
-        .map(|x| x.variable_id)
-        .collect();
-
-    // Find the result of the vote that was just concluded. We want to store the proof on the result
-    // so we need it to be mutable to update the proof field.
-    let result = state
-        .vote_results
-        .iter_mut()
-        .find(|r| r.vote_id == state.current_vote_id)
-        .unwrap();
-
     // The signatures provided by the computation nodes can be found on the data attestation object
     // in the zk state. Find the attestation that has the same id as the one provided in the
-    // arguments.
-    let attestation = zk_state
+    // arguments, and its index among the attestations requested for this vote: since attests are
+    // requested in a fixed order (see `build_and_attest_voting_result`) and `data_attestations`
+    // reflects that same request order, this index - not the order completions are delivered in,
+    // which is not guaranteed to match - tells us which pending attestation this one is.
+    let attestation_index = zk_state
         .data_attestations
         .iter()
-        .find(|a| a.attestation_id == attestation_id)
-        .unwrap();
+        .position(|a| a.attestation_id == attestation_id)
+        .expect("attestation_id must correspond to a known data attestation");
+    let attestation = &zk_state.data_attestations[attestation_index];
+    // Attestations of earlier votes come before this vote's in `data_attestations`, so offset the
+    // index by their number to get its index among the attestations requested for this vote.
+    let first_index_of_this_vote =
+        zk_state.data_attestations.len() - state.pending_attestations.len();
 
     // Parse the signatures into a text format that can be used in an Eth transaction without
     // further data conversions. The format is an array of the signatures in hex encoding.
-    let proof_of_result = format! {"[{}]", attestation
+    let proof = format! {"[{}]", attestation
     .signatures
     .iter()
     .map(as_evm_string)
     .collect::<Vec<String>>()
     .join(", ")};
 
-    // Save the proof on the result object for convenient retrieval.
-    result.proof = Some(proof_of_result);
-    // Increment the vote id.
+    let pending = state.pending_attestations[attestation_index - first_index_of_this_vote].clone();
+
+    // Find the result of the vote that was just concluded. We want to store the proof on the result
+    // so we need it to be mutable to update the proof field.
+    let result = state
+        .vote_results
+        .iter_mut()
+        .find(|r| r.vote_id == state.current_vote_id)
+        .unwrap();
+    match pending {
+        PendingAttestation::PackedResult => {
+            result.proof = Some(proof);
+        }
+        PendingAttestation::Digit {
+            candidate,
+            digit_position,
+        } => {
+            result.digit_proofs.push(DigitAttestationProof {
+                candidate,
+                digit_position,
+                digit_value: result.tally[candidate as usize] / state.digit_base.pow(digit_position)
+                    % state.digit_base,
+                proof,
+            });
+        }
+    }
+
+    // If other attestations are still outstanding for this vote, wait for them before cleaning up
+    // and starting the next vote.
+    state.attestations_remaining -= 1;
+    if state.attestations_remaining > 0 {
+        return (state, vec![], vec![]);
+    }
+
+    // Get ids of all secret variables, to delete all votes cast in the previous vote before
+    // starting the next one.
+    let variables_to_delete: Vec<SecretVarId> = zk_state
+        .secret_variables
+        .iter()
+        .map(|x| x.variable_id)
+        .collect();
+
+    // Increment the vote id, and clear the now-stale pending attestations for the next vote.
     state.current_vote_id += 1;
+    state.pending_attestations = vec![];
     // Return the tuple with the new updated state, no events, and an update to notify the runtime
     // environment to delete the variables and set the calculation status to Waiting. This ensures
     // that the contract will accept secret votes for the next round.
@@ -300,21 +527,79 @@ fn save_attestation_on_result_and_start_next_vote(
 }
 
 /// Serialize the vote result into a binary format that matches the format used by ethereum's
-/// abi.encodePacked() method, i.e. 4 32-bit unsigned integers encoded in big endian format.
+/// abi.encodePacked() method, i.e. the vote id, one 32-bit unsigned integer per candidate, the
+/// total weight, the quorum and approval threshold in effect, whether the vote passed, and finally
+/// the number of eligible voters who abstained, all encoded in big endian format.
 fn serialize_result_as_big_endian(result: VoteResult) -> Vec<u8> {
     let mut output: Vec<u8> = vec![];
     result
         .vote_id
         .rpc_write_to(&mut output)
         .expect("Unable to serialize vote_id");
+    for (candidate, count) in result.tally.iter().enumerate() {
+        count
+            .rpc_write_to(&mut output)
+            .unwrap_or_else(|_| panic!("Unable to serialize tally for candidate {candidate}"));
+    }
+    result
+        .total_weight
+        .rpc_write_to(&mut output)
+        .expect("Unable to serialize total_weight");
+    result
+        .quorum
+        .rpc_write_to(&mut output)
+        .expect("Unable to serialize quorum");
     result
-        .votes_for
+        .approval_threshold
+        .rpc_write_to(&mut output)
+        .expect("Unable to serialize approval_threshold");
+    (result.passed as u32)
         .rpc_write_to(&mut output)
-        .expect("Unable to serialize votes_for");
+        .expect("Unable to serialize passed");
     result
-        .votes_against
+        .abstained
+        .rpc_write_to(&mut output)
+        .expect("Unable to serialize abstained");
+    output
+}
+
+/// Decomposes `value` into `digit_count` base-`base` digits, most-significant digit first,
+/// zero-padded to `digit_count` digits. Each digit is paired with its position, i.e. the exponent
+/// `i` in `digit_value = (value / base^i) % base`, so that `decompose_into_digits` is invertible
+/// without assuming the order in which digits are consumed.
+fn decompose_into_digits(value: u32, base: u32, digit_count: u32) -> Vec<(u32, u32)> {
+    (0..digit_count)
+        .rev()
+        .map(|digit_position| {
+            let digit_value = (value / base.pow(digit_position)) % base;
+            (digit_position, digit_value)
+        })
+        .collect()
+}
+
+/// Serialize a single digit attestation into a binary format that matches the format used by
+/// ethereum's abi.encodePacked() method, i.e. 4 32-bit unsigned integers encoded in big endian
+/// format. Binding the vote id, candidate and digit position to the digit value prevents a digit
+/// attestation from one vote, or one candidate or position, being replayed as another.
+fn serialize_digit_attestation(
+    vote_id: u32,
+    candidate: u32,
+    digit_position: u32,
+    digit_value: u32,
+) -> Vec<u8> {
+    let mut output: Vec<u8> = vec![];
+    vote_id
+        .rpc_write_to(&mut output)
+        .expect("Unable to serialize vote_id");
+    candidate
+        .rpc_write_to(&mut output)
+        .expect("Unable to serialize candidate");
+    digit_position
         .rpc_write_to(&mut output)
-        .expect("Unable to serialize votes_against");
+        .expect("Unable to serialize digit_position");
+    digit_value
+        .rpc_write_to(&mut output)
+        .expect("Unable to serialize digit_value");
     output
 }
 
@@ -346,44 +631,169 @@ fn as_evm_string(signature: &Signature) -> String {
     format!("0x{r}{s}{recovery_id}")
 }
 
-/// Determines the result of the vote in raw numbers, by reading the number of yes votes and
-/// deriving the number of no votes.
+/// Determines the result of the vote in raw numbers, by reading the per-candidate weighted tally
+/// variables and the total weight variable opened after the computation completed, and deriving
+/// whether the vote passed from the quorum and approval threshold in effect.
+///
+/// The last of the `computation_result_variable_ids` holds the total voting weight across all
+/// ballots, the rest hold the weighted tally for each candidate in order.
 fn determine_result(
     state: &ContractState,
     zk_state: &ZkState<SecretVarMetadata>,
-    computation_result_variable_id: &SecretVarId,
+    computation_result_variable_ids: &[SecretVarId],
 ) -> VoteResult {
-    // Read the opened result of the ZK computation, which is a count of how many yes votes were
-    // cast It is stored as an unsigned 32 bit integer in little endian format.
-    let votes_for = read_variable_u32_le(zk_state, computation_result_variable_id);
-    // Count the number of secret variables of type Vote, to get total number of cast votes.
+    // Read the opened result of the ZK computation. Each value is stored as an unsigned 32 bit
+    // integer in little endian format.
+    let (total_weight_id, tally_ids) = computation_result_variable_ids
+        .split_last()
+        .expect("Computation must produce at least the total weight variable");
+    let tally = tally_ids
+        .iter()
+        .map(|id| read_variable_u32_le(zk_state, id))
+        .collect();
+    let total_weight = read_variable_u32_le(zk_state, total_weight_id);
+
+    // Count the number of ballots actually cast, to derive how many eligible voters abstained.
     let total_votes = zk_state
         .secret_variables
         .iter()
         .filter(|x| x.metadata.variable_type == SecretVarType::Vote)
         .count() as u32;
-    // Calculate the number of no votes as the number of yes votes subtracted from the total votes.
-    let votes_against = total_votes - votes_for;
-    // Build the vote result from the numbers and set the proof to None as we don't have it yet.
+    let abstained = count_abstained(state.eligible_voters.len() as u32, total_votes);
+
+    let leading_tally = tally.iter().copied().max().unwrap_or(0);
+    let passed = vote_passed(
+        total_weight,
+        leading_tally,
+        state.quorum,
+        state.approval_threshold,
+    );
+
+    // Build the vote result from the numbers. The proof and digit proofs are still missing, as
+    // attestation has not happened yet.
     VoteResult {
         vote_id: state.current_vote_id,
-        votes_for,
-        votes_against,
+        tally,
+        total_weight,
         proof: None,
+        digit_proofs: vec![],
+        passed,
+        quorum: state.quorum,
+        approval_threshold: state.approval_threshold,
+        abstained,
     }
 }
 
+/// Counts how many of the `eligible_voters` did not cast one of the `total_votes` ballots.
+fn count_abstained(eligible_voters: u32, total_votes: u32) -> u32 {
+    eligible_voters - total_votes
+}
+
+/// Determines whether a vote passes: turnout (`total_weight`) must meet `quorum`, and the leading
+/// candidate's share of the total weight must meet `approval_threshold`, expressed in parts per
+/// [`APPROVAL_THRESHOLD_DENOMINATOR`]. With no ballots cast, turnout is zero and the vote cannot
+/// pass, regardless of quorum.
+fn vote_passed(total_weight: u32, leading_tally: u32, quorum: u32, approval_threshold: u32) -> bool {
+    total_weight >= quorum
+        && total_weight > 0
+        && (u64::from(leading_tally) * u64::from(APPROVAL_THRESHOLD_DENOMINATOR)
+            / u64::from(total_weight)) as u32
+            >= approval_threshold
+}
+
 /// Reads a variable's data as an u32.
 fn read_variable_u32_le(
     zk_state: &ZkState<SecretVarMetadata>,
-    yes_count_variable_id: &SecretVarId,
+    variable_id: &SecretVarId,
 ) -> u32 {
     // Get the actual variable from state.
-    let yes_count_variable = zk_state.get_variable(*yes_count_variable_id).unwrap();
+    let variable = zk_state.get_variable(*variable_id).unwrap();
     // Defined buffer to save the variable data in.
     let mut buffer = [0u8; 4];
     // Copy the variable data to the buffer.
-    buffer.copy_from_slice(yes_count_variable.data.as_ref().unwrap().as_slice());
+    buffer.copy_from_slice(variable.data.as_ref().unwrap().as_slice());
     // Cast the variable bytes to a u32, specifying that the bytes are ordered in little endian.
     <u32>::from_le_bytes(buffer)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decompose_into_digits_pads_and_orders_most_significant_first() {
+        assert_eq!(
+            decompose_into_digits(123, 10, 5),
+            vec![(4, 0), (3, 0), (2, 1), (1, 2), (0, 3)]
+        );
+    }
+
+    #[test]
+    fn decompose_into_digits_with_zero_digit_count_is_empty() {
+        assert_eq!(decompose_into_digits(123, 10, 0), vec![]);
+    }
+
+    #[test]
+    fn serialize_digit_attestation_binds_all_four_fields() {
+        let bytes = serialize_digit_attestation(1, 2, 3, 4);
+        assert_eq!(bytes, vec![0, 0, 0, 1, 0, 0, 0, 2, 0, 0, 0, 3, 0, 0, 0, 4]);
+    }
+
+    #[test]
+    fn serialize_digit_attestation_differs_per_vote_id() {
+        assert_ne!(
+            serialize_digit_attestation(1, 0, 0, 0),
+            serialize_digit_attestation(2, 0, 0, 0)
+        );
+    }
+
+    #[test]
+    fn count_abstained_subtracts_votes_cast_from_eligible_voters() {
+        assert_eq!(count_abstained(10, 4), 6);
+        assert_eq!(count_abstained(5, 5), 0);
+    }
+
+    #[test]
+    fn vote_passed_requires_quorum_and_approval_threshold() {
+        // Turnout below quorum fails regardless of the leading candidate's share.
+        assert!(!vote_passed(5, 5, 10, 5_000));
+        // Turnout meets quorum but leading candidate's share is below the approval threshold.
+        assert!(!vote_passed(10, 4, 10, 5_000));
+        // Turnout meets quorum and leading candidate's share meets the approval threshold.
+        assert!(vote_passed(10, 5, 10, 5_000));
+    }
+
+    #[test]
+    fn vote_passed_is_false_with_no_ballots_cast() {
+        assert!(!vote_passed(0, 0, 0, 0));
+    }
+
+    #[test]
+    fn serialize_result_as_big_endian_includes_abstained() {
+        let result = VoteResult {
+            vote_id: 1,
+            tally: vec![2, 3],
+            total_weight: 5,
+            proof: None,
+            digit_proofs: vec![],
+            passed: true,
+            quorum: 1,
+            approval_threshold: 5_000,
+            abstained: 7,
+        };
+        let bytes = serialize_result_as_big_endian(result);
+        assert_eq!(
+            bytes,
+            vec![
+                0, 0, 0, 1, // vote_id
+                0, 0, 0, 2, // tally[0]
+                0, 0, 0, 3, // tally[1]
+                0, 0, 0, 5, // total_weight
+                0, 0, 0, 1, // quorum
+                0, 0, 19, 136, // approval_threshold
+                0, 0, 0, 1, // passed
+                0, 0, 0, 7, // abstained
+            ]
+        );
+    }
+}
@@ -1,20 +1,41 @@
 use pbc_zk::*;
 
-/// Perform a zk computation on secret-shared data to count the number
-/// of accepting votes (non-zero).
+/// The number of candidates a ballot can choose between. Each secret vote carries a candidate
+/// index in `0..NUM_CANDIDATES`, and the tally produced below has one entry per candidate.
+pub const NUM_CANDIDATES: usize = 8;
+
+/// A secret ballot: a candidate index together with the voter's secret voting weight (e.g. token
+/// or stake balance). Mirrors the `SecretVote` input type declared by the contract.
+#[derive(SecretBinary)]
+pub(crate) struct SecretVote {
+    pub(crate) candidate: Sbi8,
+    pub(crate) weight: Sbi32,
+}
+
+/// Perform a zk computation on secret-shared data to tally the weighted votes cast for each
+/// candidate, without revealing any individual voter's weight.
+///
+/// Each secret variable holds the candidate index the voter chose together with their secret
+/// voting weight. For every candidate `c` in `0..NUM_CANDIDATES`, the tally accumulates the
+/// weight of each ballot cast for `c`. The total weight across all ballots is also accumulated, so
+/// that turnout can be attested without revealing it is the sum of individually secret weights.
 ///
 /// ### Returns:
 ///
-/// The number of accepting votes.
-pub fn zk_compute() -> Sbi32 {
-    // Initialize votes
-    let mut votes_for: Sbi32 = Sbi32::from(0);
-    let one: Sbi1 = Sbi8::from(0i8) == Sbi8::from(0i8);
+/// The weighted tally for each candidate, indexed by candidate number, followed by the total
+/// weight of all ballots cast.
+pub fn zk_compute() -> [Sbi32; NUM_CANDIDATES + 1] {
+    // Initialize tallies, with the last slot reserved for the total weight of all ballots.
+    let mut votes: [Sbi32; NUM_CANDIDATES + 1] = [Sbi32::from(0); NUM_CANDIDATES + 1];
     // Count votes
     for variable_id in secret_variable_ids() {
-        if load_sbi::<Sbi1>(variable_id) == one {
-            votes_for = votes_for + Sbi32::from(1);
+        let ballot = load_sbi::<SecretVote>(variable_id);
+        for c in 0..NUM_CANDIDATES {
+            if ballot.candidate == Sbi8::from(c as i8) {
+                votes[c] = votes[c] + ballot.weight;
+            }
         }
+        votes[NUM_CANDIDATES] = votes[NUM_CANDIDATES] + ballot.weight;
     }
-    votes_for
+    votes
 }
@@ -7,37 +7,40 @@ mod tests {
     use pbc_zk::api::*;
     use pbc_zk::*;
 
-    use crate::zk_compute::zk_compute;
+    use crate::zk_compute::{zk_compute, SecretVote};
 
     #[test]
-    fn zk_compute_zero_one() {
-        // assert eval(0,0,1,1,0,0) => 2
-        let zero: Sbi1 = Sbi8::from(0i8) == Sbi8::from(1i8);
-        let one: Sbi1 = Sbi8::from(0i8) == Sbi8::from(0i8);
+    fn zk_compute_tallies_weighted_votes_per_candidate() {
+        // candidate 0 gets weights 2 and 1, candidate 1 gets weight 5, candidate 2 gets weight 3
+        // assert eval((0,2),(0,1),(1,5),(2,3)) => [3, 5, 3, 0, 0, 0, 0, 0, 11]
         let inputs: Vec<SecretVar> = vec![
             SecretVar {
                 metadata: Box::new(1),
-                value: Box::new(zero),
+                value: Box::new(SecretVote {
+                    candidate: Sbi8::from(0i8),
+                    weight: Sbi32::from(2),
+                }),
             },
             SecretVar {
                 metadata: Box::new(2),
-                value: Box::new(zero),
+                value: Box::new(SecretVote {
+                    candidate: Sbi8::from(0i8),
+                    weight: Sbi32::from(1),
+                }),
             },
             SecretVar {
                 metadata: Box::new(3),
-                value: Box::new(one),
+                value: Box::new(SecretVote {
+                    candidate: Sbi8::from(1i8),
+                    weight: Sbi32::from(5),
+                }),
             },
             SecretVar {
                 metadata: Box::new(4),
-                value: Box::new(one),
-            },
-            SecretVar {
-                metadata: Box::new(5),
-                value: Box::new(zero),
-            },
-            SecretVar {
-                metadata: Box::new(6),
-                value: Box::new(zero),
+                value: Box::new(SecretVote {
+                    candidate: Sbi8::from(2i8),
+                    weight: Sbi32::from(3),
+                }),
             },
         ];
 
@@ -45,6 +48,19 @@ mod tests {
             set_secrets(inputs);
         }
         let output = zk_compute();
-        assert_eq!(output, Sbi32::from(2));
+        assert_eq!(
+            output,
+            [
+                Sbi32::from(3),
+                Sbi32::from(5),
+                Sbi32::from(3),
+                Sbi32::from(0),
+                Sbi32::from(0),
+                Sbi32::from(0),
+                Sbi32::from(0),
+                Sbi32::from(0),
+                Sbi32::from(11),
+            ]
+        );
     }
 }